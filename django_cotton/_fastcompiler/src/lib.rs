@@ -2,6 +2,13 @@ use std::collections::HashSet;
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use serde::Serialize;
+
+/// Elements whose content is raw text: a literal `<c-foo>` written inside
+/// one of these should never be parsed as a cotton tag (mirrors the
+/// raw-text element list streaming HTML tokenizers use for `<script>`,
+/// `<style>`, etc.).
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
 
 #[derive(Clone)]
 struct Ignorable {
@@ -14,10 +21,28 @@ struct ProcessedResult {
     dependencies: Vec<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 struct Attribute {
     key: String,
     value: Option<String>,
+    /// The prefix stripped from `key` during parsing, e.g. `Some(":")` for
+    /// a `:title="user.name"` binding. `None` for a plain attribute.
+    namespace: Option<String>,
+}
+
+/// A node in the JSON AST returned by `parse_to_ast`. `kind` is one of
+/// `component`, `slot`, `vars`, `text`, or `ignorable`; `name` is the
+/// component/slot name (`None` for `text`/`ignorable`/`vars`); `children`
+/// holds nested nodes for paired tags, mirroring how AST-based parsers like
+/// comrak expose a navigable node tree.
+#[derive(Serialize)]
+struct AstNode {
+    kind: &'static str,
+    name: Option<String>,
+    attributes: Vec<Attribute>,
+    start: usize,
+    end: usize,
+    children: Vec<AstNode>,
 }
 
 #[derive(Clone)]
@@ -53,15 +78,40 @@ fn get_dependencies(py: Python<'_>, html: &str) -> PyResult<Vec<String>> {
     Ok(result.dependencies)
 }
 
+/// Returns a JSON-serialized AST of `html`'s component tree, for editor
+/// tooling (language servers, linting) to resolve component references and
+/// find unclosed tags without re-implementing cotton's grammar in Python.
+#[pyfunction]
+fn parse_to_ast(py: Python<'_>, html: &str) -> PyResult<String> {
+    let html_owned = html.to_owned();
+    let nodes = py.allow_threads(|| build_ast(&html_owned))?;
+    serde_json::to_string(&nodes).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
 fn process_internal(html: &str) -> PyResult<ProcessedResult> {
     let (processed_html, ignorables) = exclude_ignorables(html);
-    let (vars_content, processed_html) = process_c_vars(&processed_html)?;
-    let (replacements, dependencies) = collect_replacements(&processed_html)?;
-
-    let mut compiled = processed_html.clone();
-    for (original, replacement) in replacements {
-        compiled = compiled.replace(&original, &replacement);
+    let (vars_content, vars_span) = process_c_vars(&processed_html)?;
+    let (mut spans, dependencies) = collect_replacements(&processed_html)?;
+
+    if let Some((start, end)) = vars_span {
+        // collect_replacements scans the same buffer the <c-vars> element
+        // still lives in, so it may have emitted spans for c- tags nested
+        // inside the <c-vars>...</c-vars> body. Those spans overlap the
+        // span that removes the whole element below; drop them so the
+        // splice loop never sees two spans covering the same range.
+        spans.retain(|(span_start, span_end, _)| *span_end <= start || *span_start >= end);
+        spans.push((start, end, String::new()));
     }
+    spans.sort_by_key(|(start, _, _)| *start);
+
+    let mut compiled = String::with_capacity(processed_html.len());
+    let mut cursor = 0;
+    for (start, end, replacement) in spans {
+        compiled.push_str(&processed_html[cursor..start]);
+        compiled.push_str(&replacement);
+        cursor = end;
+    }
+    compiled.push_str(&processed_html[cursor..]);
 
     if let Some(vars) = vars_content {
         compiled = format!("{}{}{{% endvars %}}", vars, compiled);
@@ -197,19 +247,29 @@ fn extract_verbatim_inner(content: &str) -> &str {
     content
 }
 
-fn process_c_vars(html: &str) -> PyResult<(Option<String>, String)> {
+/// Locates the single `<c-vars ...>` element (if any) and reports it as a
+/// `(start, end)` span into `html` rather than rewriting the string, so the
+/// span stays valid for `process_internal` to splice against the same
+/// canonical buffer that `collect_replacements` works from.
+fn process_c_vars(html: &str) -> PyResult<(Option<String>, Option<(usize, usize)>)> {
     let mut vars_content: Option<String> = None;
-    let mut output = String::with_capacity(html.len());
+    let mut vars_span: Option<(usize, usize)> = None;
     let mut index = 0;
 
-    while let Some(pos) = html[index..].find("<c-vars") {
-        let absolute = index + pos;
-        output.push_str(&html[index..absolute]);
+    while index < html.len() {
+        let Some(next_lt_rel) = html[index..].find('<') else {
+            break;
+        };
+        let absolute = index + next_lt_rel;
+
+        if let Some(raw_end) = match_raw_text_block(html, absolute) {
+            index = raw_end;
+            continue;
+        }
 
         match parse_c_tag(html, absolute) {
             Ok(Some(tag)) => {
                 if tag.name != "vars" {
-                    output.push_str(&tag.original);
                     index = tag.end;
                     continue;
                 }
@@ -230,35 +290,228 @@ fn process_c_vars(html: &str) -> PyResult<(Option<String>, String)> {
                 vars_content = Some(format!("{{% vars {} %}}", attrs_text));
 
                 if tag.is_self_closing {
+                    vars_span = Some((tag.start, tag.end));
                     index = tag.end;
+                } else if let Some(close_rel) = html[tag.end..].find("</c-vars>") {
+                    let close_end = tag.end + close_rel + "</c-vars>".len();
+                    vars_span = Some((tag.start, close_end));
+                    index = close_end;
                 } else {
-                    if let Some(close_rel) = html[tag.end..].find("</c-vars>") {
-                        let close_end = tag.end + close_rel + "</c-vars>".len();
-                        index = close_end;
-                    } else {
-                        return Err(PyValueError::new_err("Missing closing </c-vars> tag."));
-                    }
+                    return Err(PyValueError::new_err("Missing closing </c-vars> tag."));
                 }
             }
             Ok(None) => {
-                output.push_str(&html[absolute..absolute + 1]);
                 index = absolute + 1;
             }
-            Err(msg) => {
-                return Err(build_py_error(&msg, html, absolute));
+            Err((msg, err_end)) => {
+                return Err(build_py_error(&msg, html, absolute, err_end));
             }
         }
     }
 
-    output.push_str(&html[index..]);
+    Ok((vars_content, vars_span))
+}
 
-    Ok((vars_content, output))
+/// A still-open element while `build_ast` walks the source: the opening
+/// tag has been seen and pushed, and children accumulate here until its
+/// matching closing tag (or EOF) is reached. `tag_name` is the `c-` tag
+/// name used to match against closing tags (e.g. always `"slot"` for a
+/// `c-slot`, regardless of its `name=` attribute); `name` is the node's
+/// display name in the serialized AST. `tag_end` is the opening tag's own
+/// end offset, used to point an "unclosed tag" error at the tag itself
+/// rather than at the whole unclosed region.
+struct AstFrame {
+    kind: &'static str,
+    tag_name: String,
+    name: Option<String>,
+    attributes: Vec<Attribute>,
+    start: usize,
+    tag_end: usize,
+    children: Vec<AstNode>,
 }
 
-fn collect_replacements(html: &str) -> PyResult<(Vec<(String, String)>, Vec<String>)> {
-    let mut replacements = Vec::new();
+fn attach_ast_node(roots: &mut Vec<AstNode>, stack: &mut [AstFrame], node: AstNode) {
+    if let Some(top) = stack.last_mut() {
+        top.children.push(node);
+    } else {
+        roots.push(node);
+    }
+}
+
+fn flush_ast_text(roots: &mut Vec<AstNode>, stack: &mut [AstFrame], start: usize, end: usize) {
+    if start < end {
+        attach_ast_node(
+            roots,
+            stack,
+            AstNode {
+                kind: "text",
+                name: None,
+                attributes: Vec::new(),
+                start,
+                end,
+                children: Vec::new(),
+            },
+        );
+    }
+}
+
+/// Promotes the flat tag scan used by `collect_replacements` into a
+/// nesting-aware tree: each non-self-closing `c-` tag other than `c-vars` is
+/// pushed as an `AstFrame` and popped once its closing tag is found, with
+/// everything seen in between becoming its children. A `c-vars` element,
+/// paired or not, becomes a single leaf `vars` node. Text and Django `{% %}`/`{{ }}`/
+/// `{# #}` regions become leaf `text`/`ignorable` nodes.
+fn build_ast(html: &str) -> PyResult<Vec<AstNode>> {
+    let mut roots: Vec<AstNode> = Vec::new();
+    let mut stack: Vec<AstFrame> = Vec::new();
+    let mut index = 0;
+    let mut text_start = 0;
+
+    while index < html.len() {
+        if let Some((end, _content)) = match_ignorable(html, index) {
+            flush_ast_text(&mut roots, &mut stack, text_start, index);
+            attach_ast_node(
+                &mut roots,
+                &mut stack,
+                AstNode {
+                    kind: "ignorable",
+                    name: None,
+                    attributes: Vec::new(),
+                    start: index,
+                    end,
+                    children: Vec::new(),
+                },
+            );
+            index = end;
+            text_start = index;
+            continue;
+        }
+
+        if html.as_bytes()[index] != b'<' {
+            let ch = html[index..].chars().next().unwrap();
+            index += ch.len_utf8();
+            continue;
+        }
+
+        if let Some(raw_end) = match_raw_text_block(html, index) {
+            index = raw_end;
+            continue;
+        }
+
+        match parse_c_tag(html, index) {
+            Ok(Some(tag)) => {
+                flush_ast_text(&mut roots, &mut stack, text_start, tag.start);
+
+                if tag.is_closing {
+                    let open_name = stack.last().map(|frame| frame.tag_name.as_str());
+                    check_closing_tag_name(open_name, &tag, html)?;
+                    let frame = stack.pop().unwrap();
+                    attach_ast_node(&mut roots, &mut stack, finish_ast_frame(frame, tag.end));
+                    index = tag.end;
+                    text_start = index;
+                    continue;
+                }
+
+                if tag.name == "vars" {
+                    let end = if tag.is_self_closing {
+                        tag.end
+                    } else if let Some(close_rel) = html[tag.end..].find("</c-vars>") {
+                        tag.end + close_rel + "</c-vars>".len()
+                    } else {
+                        tag.end
+                    };
+                    attach_ast_node(
+                        &mut roots,
+                        &mut stack,
+                        AstNode {
+                            kind: "vars",
+                            name: None,
+                            attributes: tag.attributes,
+                            start: tag.start,
+                            end,
+                            children: Vec::new(),
+                        },
+                    );
+                    index = end;
+                    text_start = index;
+                    continue;
+                }
+
+                let kind = if tag.name == "slot" { "slot" } else { "component" };
+                let name = if tag.name == "slot" {
+                    slot_display_name(&tag)
+                } else {
+                    Some(tag.name.clone())
+                };
+
+                if tag.is_self_closing {
+                    attach_ast_node(
+                        &mut roots,
+                        &mut stack,
+                        AstNode {
+                            kind,
+                            name,
+                            attributes: tag.attributes,
+                            start: tag.start,
+                            end: tag.end,
+                            children: Vec::new(),
+                        },
+                    );
+                } else {
+                    stack.push(AstFrame {
+                        kind,
+                        tag_name: tag.name.clone(),
+                        name,
+                        attributes: tag.attributes,
+                        start: tag.start,
+                        tag_end: tag.end,
+                        children: Vec::new(),
+                    });
+                }
+
+                index = tag.end;
+                text_start = index;
+            }
+            Ok(None) => {
+                index += 1;
+            }
+            Err((msg, err_end)) => {
+                return Err(build_py_error(&msg, html, index, err_end));
+            }
+        }
+    }
+
+    flush_ast_text(&mut roots, &mut stack, text_start, html.len());
+
+    if let Some(frame) = stack.pop() {
+        return Err(unclosed_tag_error(&frame.tag_name, frame.start, frame.tag_end, html));
+    }
+
+    Ok(roots)
+}
+
+fn finish_ast_frame(frame: AstFrame, end: usize) -> AstNode {
+    AstNode {
+        kind: frame.kind,
+        name: frame.name,
+        attributes: frame.attributes,
+        start: frame.start,
+        end,
+        children: frame.children,
+    }
+}
+
+/// Scans `html` for `c-` tags and reports each one as a `(start, end,
+/// replacement)` span rather than an `(original, replacement)` string pair,
+/// so `process_internal` can splice every replacement into the source
+/// buffer in a single pass instead of looping `String::replace` over it.
+/// Spans are returned in source order, which is also the order
+/// `process_internal` needs them in to walk the buffer left to right.
+fn collect_replacements(html: &str) -> PyResult<(Vec<(usize, usize, String)>, Vec<String>)> {
+    let mut spans = Vec::new();
     let mut dependencies = Vec::new();
     let mut seen_deps = HashSet::new();
+    let mut open_stack: Vec<(String, usize, usize)> = Vec::new();
     let mut index = 0;
 
     while index < html.len() {
@@ -267,6 +520,11 @@ fn collect_replacements(html: &str) -> PyResult<(Vec<(String, String)>, Vec<Stri
         };
         let absolute = index + next_lt_rel;
 
+        if let Some(raw_end) = match_raw_text_block(html, absolute) {
+            index = raw_end;
+            continue;
+        }
+
         match parse_c_tag(html, absolute) {
             Ok(Some(tag)) => {
                 index = tag.end;
@@ -280,24 +538,40 @@ fn collect_replacements(html: &str) -> PyResult<(Vec<(String, String)>, Vec<Stri
                         continue;
                     }
                     "slot" => {
+                        if tag.is_closing {
+                            check_closing_tag(&mut open_stack, &tag, html)?;
+                            spans.push((tag.start, tag.end, "{% endslot %}".to_string()));
+                            continue;
+                        }
                         match process_slot(&tag) {
                             Ok(replacement) => {
-                                replacements.push((tag.original.clone(), replacement));
+                                spans.push((tag.start, tag.end, replacement));
+                                if !tag.is_self_closing {
+                                    open_stack.push((tag.name.clone(), tag.start, tag.end));
+                                }
                             }
-                            Err(msg) => return Err(build_py_error(&msg, html, tag.start)),
+                            Err(msg) => return Err(build_py_error(&msg, html, tag.start, tag.end)),
                         }
                     }
                     _ => {
+                        if tag.is_closing {
+                            check_closing_tag(&mut open_stack, &tag, html)?;
+                            spans.push((tag.start, tag.end, "{% endc %}".to_string()));
+                            continue;
+                        }
                         match process_component(&tag) {
                             Ok((replacement, dependency)) => {
-                                replacements.push((tag.original.clone(), replacement));
+                                spans.push((tag.start, tag.end, replacement));
+                                if !tag.is_self_closing {
+                                    open_stack.push((tag.name.clone(), tag.start, tag.end));
+                                }
                                 if let Some(dep) = dependency {
                                     if seen_deps.insert(dep.clone()) {
                                         dependencies.push(dep);
                                     }
                                 }
                             }
-                            Err(msg) => return Err(build_py_error(&msg, html, tag.start)),
+                            Err(msg) => return Err(build_py_error(&msg, html, tag.start, tag.end)),
                         }
                     }
                 }
@@ -305,16 +579,81 @@ fn collect_replacements(html: &str) -> PyResult<(Vec<(String, String)>, Vec<Stri
             Ok(None) => {
                 index = absolute + 1;
             }
-            Err(msg) => {
-                return Err(build_py_error(&msg, html, absolute));
+            Err((msg, err_end)) => {
+                return Err(build_py_error(&msg, html, absolute, err_end));
             }
         }
     }
 
-    Ok((replacements, dependencies))
+    if let Some((name, start, end)) = open_stack.pop() {
+        return Err(unclosed_tag_error(&name, start, end, html));
+    }
+
+    Ok((spans, dependencies))
+}
+
+/// Builds the `PyValueError` for a tag that is still open when the template
+/// ends, reached the end of the template without a matching `</c-name>`.
+/// Shared by `collect_replacements` and `build_ast` so both report the same
+/// message for the same condition.
+fn unclosed_tag_error(name: &str, start: usize, end: usize, html: &str) -> PyErr {
+    build_py_error(
+        &format!(
+            "Unclosed tag <c-{}>: reached the end of the template without a matching </c-{}>.",
+            name, name
+        ),
+        html,
+        start,
+        end,
+    )
+}
+
+/// Checks that a closing tag's name matches the innermost still-open tag,
+/// naming both the offending closing tag and the tag it failed to close
+/// when it doesn't. `open_name` is `None` when nothing is open at all.
+/// Shared by `check_closing_tag` (which pops `collect_replacements`'s
+/// stack) and `build_ast` (which only peeks before popping its own stack).
+fn check_closing_tag_name(open_name: Option<&str>, tag: &ParsedTag, html: &str) -> PyResult<()> {
+    match open_name {
+        Some(open_name) if open_name == tag.name => Ok(()),
+        Some(open_name) => Err(build_py_error(
+            &format!(
+                "Mismatched closing tag </c-{}>: the innermost open tag is <c-{}>, which must be closed first.",
+                tag.name, open_name
+            ),
+            html,
+            tag.start,
+            tag.end,
+        )),
+        None => Err(build_py_error(
+            &format!(
+                "Closing tag </c-{}> does not match any open <c-{}> tag.",
+                tag.name, tag.name
+            ),
+            html,
+            tag.start,
+            tag.end,
+        )),
+    }
+}
+
+/// Pops the innermost open tag and checks it matches `tag`'s name. This is
+/// what turns an unbalanced `<c-foo>...</c-bar>` from silently-broken Django
+/// output into an actionable `PyValueError`.
+fn check_closing_tag(
+    open_stack: &mut Vec<(String, usize, usize)>,
+    tag: &ParsedTag,
+    html: &str,
+) -> PyResult<()> {
+    let open_name = open_stack.pop();
+    check_closing_tag_name(open_name.as_ref().map(|(name, _, _)| name.as_str()), tag, html)
 }
 
-fn parse_c_tag(html: &str, start: usize) -> Result<Option<ParsedTag>, String> {
+/// Parses the `c-` tag at `html[start..]`, if any. On a malformed tag the
+/// error carries not just a message but how far the scan got before it
+/// failed, so callers can underline the whole offending region (e.g. the
+/// entire unterminated tag up to EOF) instead of a single caret under `<`.
+fn parse_c_tag(html: &str, start: usize) -> Result<Option<ParsedTag>, (String, usize)> {
     if !html[start..].starts_with('<') {
         return Ok(None);
     }
@@ -344,7 +683,7 @@ fn parse_c_tag(html: &str, start: usize) -> Result<Option<ParsedTag>, String> {
     }
 
     if idx == name_start {
-        return Err("c- tag missing component name".to_string());
+        return Err(("c- tag missing component name".to_string(), idx));
     }
 
     let name = html[name_start..idx].to_string();
@@ -367,7 +706,7 @@ fn parse_c_tag(html: &str, start: usize) -> Result<Option<ParsedTag>, String> {
     }
 
     if pos >= html.len() {
-        return Err("Unterminated c- tag".to_string());
+        return Err(("Unterminated c- tag".to_string(), html.len()));
     }
 
     let tag_end = pos + 1;
@@ -414,6 +753,125 @@ fn parse_c_tag(html: &str, start: usize) -> Result<Option<ParsedTag>, String> {
     }))
 }
 
+/// If `html[start..]` opens one of `RAW_TEXT_ELEMENTS`, consumes everything
+/// up to and including the matching case-insensitive closing tag and
+/// returns the end of that closing tag so the caller can skip the whole
+/// block without ever attempting `c-` tag parsing inside it. Nesting of the
+/// same tag name is not tracked: the first matching closing tag ends the
+/// block, even after another opening tag of the same name inside it.
+/// Returns `None` when `start`
+/// doesn't open a raw-text element, or when the block is never closed (in
+/// which case the caller falls back to its normal char-by-char scan).
+fn match_raw_text_block(html: &str, start: usize) -> Option<usize> {
+    let after_lt = start + 1;
+    if after_lt >= html.len() || html.as_bytes()[after_lt] == b'/' {
+        return None;
+    }
+
+    let name_start = after_lt;
+    let mut idx = name_start;
+    while idx < html.len() {
+        let ch = html.as_bytes()[idx];
+        if ch.is_ascii_whitespace() || ch == b'>' || ch == b'/' {
+            break;
+        }
+        idx += 1;
+    }
+    if idx == name_start {
+        return None;
+    }
+
+    let name = html[name_start..idx].to_ascii_lowercase();
+    if !RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+        return None;
+    }
+
+    let mut pos = idx;
+    let mut in_quote: Option<char> = None;
+    while pos < html.len() {
+        let ch = html.as_bytes()[pos] as char;
+        if ch == '"' || ch == '\'' {
+            if Some(ch) == in_quote {
+                in_quote = None;
+            } else if in_quote.is_none() {
+                in_quote = Some(ch);
+            }
+        } else if ch == '>' && in_quote.is_none() {
+            break;
+        }
+        pos += 1;
+    }
+    if pos >= html.len() {
+        return None;
+    }
+
+    let is_self_closing = pos > idx && html.as_bytes()[pos - 1] == b'/';
+    let tag_end = pos + 1;
+    if is_self_closing {
+        return Some(tag_end);
+    }
+
+    let (_, close_end) = find_closing_tag(html, tag_end, &name)?;
+    Some(close_end)
+}
+
+/// Finds the next `</name ...>` tag at or after `from`, matching `name`
+/// case-insensitively, and returns its `(start, end)` span. Used by
+/// `match_raw_text_block` to find the first closing tag of the raw-text
+/// element it opened. Real raw-text elements (`script`/`style`/`textarea`/
+/// `title`) never nest, so unlike a general-purpose HTML scanner this never
+/// needs to track nesting depth against matching opening tags: the first
+/// closing tag found, however the content in between looks, is the end of
+/// the element.
+fn find_closing_tag(html: &str, from: usize, name: &str) -> Option<(usize, usize)> {
+    let mut pos = from;
+
+    loop {
+        let relative = html[pos..].find('<')?;
+        let tag_start = pos + relative;
+
+        let mut idx = tag_start + 1;
+        if idx >= html.len() || html.as_bytes()[idx] != b'/' {
+            pos = tag_start + 1;
+            continue;
+        }
+        idx += 1;
+
+        let name_start = idx;
+        while idx < html.len() {
+            let ch = html.as_bytes()[idx];
+            if ch.is_ascii_whitespace() || ch == b'>' || ch == b'/' {
+                break;
+            }
+            idx += 1;
+        }
+
+        if html[name_start..idx].eq_ignore_ascii_case(name) {
+            let mut end = idx;
+            let mut in_quote: Option<char> = None;
+            while end < html.len() {
+                let ch = html.as_bytes()[end] as char;
+                if ch == '"' || ch == '\'' {
+                    if Some(ch) == in_quote {
+                        in_quote = None;
+                    } else if in_quote.is_none() {
+                        in_quote = Some(ch);
+                    }
+                } else if ch == '>' && in_quote.is_none() {
+                    break;
+                }
+                end += 1;
+            }
+            if end >= html.len() {
+                return None;
+            }
+            return Some((tag_start, end + 1));
+        }
+
+        pos = tag_start + 1;
+    }
+}
+
 fn parse_attributes(attrs: &str) -> Vec<Attribute> {
     let mut attributes = Vec::new();
     let mut index = 0;
@@ -437,14 +895,18 @@ fn parse_attributes(attrs: &str) -> Vec<Attribute> {
             break;
         }
 
-        let key = attrs[key_start..index].to_string();
+        let raw_key = attrs[key_start..index].to_string();
+        let (namespace, key) = match raw_key.strip_prefix(':') {
+            Some(rest) => (Some(":".to_string()), rest.to_string()),
+            None => (None, raw_key),
+        };
         index = skip_whitespace(attrs, index);
 
         if index < attrs.len() && attrs.as_bytes()[index] == b'=' {
             index += 1;
             index = skip_whitespace(attrs, index);
             if index >= attrs.len() {
-                attributes.push(Attribute { key, value: None });
+                attributes.push(Attribute { key, value: None, namespace });
                 break;
             }
 
@@ -464,7 +926,7 @@ fn parse_attributes(attrs: &str) -> Vec<Attribute> {
                 if index < attrs.len() {
                     index += 1;
                 }
-                attributes.push(Attribute { key, value: Some(value) });
+                attributes.push(Attribute { key, value: Some(value), namespace });
             } else {
                 let value_start = index;
                 while index < attrs.len() {
@@ -475,16 +937,27 @@ fn parse_attributes(attrs: &str) -> Vec<Attribute> {
                     index += 1;
                 }
                 let value = attrs[value_start..index].to_string();
-                attributes.push(Attribute { key, value: Some(value) });
+                attributes.push(Attribute { key, value: Some(value), namespace });
             }
         } else {
-            attributes.push(Attribute { key, value: None });
+            attributes.push(Attribute { key, value: None, namespace });
         }
     }
 
     attributes
 }
 
+/// Looks up a `c-slot` tag's `name="..."` attribute for display purposes in
+/// `build_ast`'s output, mirroring `process_slot`'s lookup but returning
+/// `None` instead of erroring when it's absent (validating that is
+/// `collect_replacements`'s job, via `process_slot`, not the AST's).
+fn slot_display_name(tag: &ParsedTag) -> Option<String> {
+    tag.attributes
+        .iter()
+        .find(|attribute| attribute.key == "name")
+        .and_then(|attribute| attribute.value.clone())
+}
+
 fn process_slot(tag: &ParsedTag) -> Result<String, String> {
     if tag.is_closing {
         return Ok("{% endslot %}".to_string());
@@ -555,7 +1028,7 @@ fn build_attribute_strings(attributes: &[Attribute]) -> (String, String) {
         match &attribute.value {
             None => processed.push(attribute.key.clone()),
             Some(value) => {
-                if should_extract(value) {
+                if attribute.namespace.is_some() || should_extract(value) {
                     extracted.push_str(&format!(
                         "{{% attr {} %}}{}{{% endattr %}}",
                         attribute.key, value
@@ -583,9 +1056,35 @@ fn should_extract(value: &str) -> bool {
         || value.contains("__COTTON_IGNORE_")
 }
 
-fn build_py_error(message: &str, html: &str, position: usize) -> PyErr {
-    let line = html[..position].chars().filter(|ch| *ch == '\n').count() + 1;
-    PyValueError::new_err(format!("Error in template at line {}: {}", line, message))
+/// Builds a `PyValueError` for the template region `start..end`, reporting
+/// a 1-based line and column (the column is a character offset from the
+/// last newline, not a byte offset) and a source excerpt with a caret
+/// underlining the span on its first line, e.g.:
+///
+/// ```text
+/// Error at line 3, col 9:
+///           <c-card title="x">
+///           ^^^^^^^^^^^^^^^^^^ <message>
+/// ```
+fn build_py_error(message: &str, html: &str, start: usize, end: usize) -> PyErr {
+    let line = html[..start].chars().filter(|ch| *ch == '\n').count() + 1;
+    let line_start = html[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = html[start..]
+        .find('\n')
+        .map(|offset| start + offset)
+        .unwrap_or(html.len());
+    let col = html[line_start..start].chars().count() + 1;
+
+    let source_line = &html[line_start..line_end];
+    let underline_end = end.min(line_end).max(start);
+    let underline_len = html[start..underline_end].chars().count().max(1);
+    let indent = " ".repeat(col - 1);
+    let caret = "^".repeat(underline_len);
+
+    PyValueError::new_err(format!(
+        "Error at line {}, col {}:\n  {}\n  {}{} {}",
+        line, col, source_line, indent, caret, message
+    ))
 }
 
 #[pymodule]
@@ -593,5 +1092,119 @@ fn _fastcompiler(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(process, m)?)?;
     m.add_function(wrap_pyfunction!(process_with_dependencies, m)?)?;
     m.add_function(wrap_pyfunction!(get_dependencies, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_to_ast, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vars_with_nested_tag_does_not_panic() {
+        let result =
+            process_internal(r#"<c-vars title="x"><c-icon name="x" /></c-vars>"#).unwrap();
+        assert!(!result.compiled.contains("c-vars"));
+        assert!(!result.compiled.contains("c-icon"));
+        assert!(result.compiled.contains("{% vars title=\"x\" %}"));
+    }
+
+    #[test]
+    fn raw_text_element_does_not_nest() {
+        // A real <script> block never nests, so a literal "<script>" inside
+        // a JS string must not fool the scanner into looking for a second
+        // closing tag; the first </script> ends the block.
+        let html = r#"<script>var s = "<script>";</script><c-card title="x" />"#;
+        let result = process_internal(html).unwrap();
+        assert!(result.compiled.contains(r#"var s = "<script>";"#));
+        assert!(result.compiled.contains("{% c "));
+    }
+
+    #[test]
+    fn process_c_vars_ignores_literal_text_inside_script() {
+        let html = r#"<script>var s = "<c-vars x></c-vars>";</script><c-vars title="x" />"#;
+        let (vars_content, vars_span) = process_c_vars(html).unwrap();
+        assert_eq!(vars_content, Some("{% vars title=\"x\" %}".to_string()));
+        assert!(vars_span.is_some());
+    }
+
+    #[test]
+    fn build_ast_balanced_tags() {
+        let nodes = build_ast(r#"<c-card title="x"><c-slot name="body">hi</c-slot></c-card>"#)
+            .unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].kind, "component");
+        assert_eq!(nodes[0].children.len(), 1);
+        assert_eq!(nodes[0].children[0].kind, "slot");
+        assert_eq!(nodes[0].children[0].name.as_deref(), Some("body"));
+    }
+
+    #[test]
+    fn build_ast_rejects_mismatched_closing_tag() {
+        let err = match build_ast("<c-card><c-icon></c-card>") {
+            Err(err) => err,
+            Ok(_) => panic!("expected a mismatched closing tag error"),
+        };
+        assert!(err.to_string().contains("Mismatched closing tag"));
+    }
+
+    #[test]
+    fn build_ast_rejects_unclosed_tag_at_eof() {
+        let err = match build_ast("<c-card>hi") {
+            Err(err) => err,
+            Ok(_) => panic!("expected an unclosed tag error"),
+        };
+        assert!(err.to_string().contains("Unclosed tag <c-card>"));
+    }
+
+    #[test]
+    fn unterminated_tag_error_underlines_whole_scanned_region() {
+        let html = "<c-card title=\"x\"\nstill going";
+        let err = collect_replacements(html).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Unterminated c- tag"));
+        // Before this fix the caret was a single "^" under the opening `<`;
+        // it must now underline the whole span the scanner covered before
+        // giving up, not just its first byte.
+        let caret_line = message.lines().find(|line| line.contains('^')).unwrap();
+        let carets = caret_line.chars().filter(|ch| *ch == '^').count();
+        assert!(carets > 1, "expected a multi-character underline, got: {caret_line}");
+    }
+
+    #[test]
+    fn collect_replacements_accepts_balanced_tags() {
+        let (spans, _) = collect_replacements("<c-card><c-icon /></c-card>").unwrap();
+        assert_eq!(spans.len(), 3);
+    }
+
+    #[test]
+    fn collect_replacements_rejects_unclosed_tag_at_eof() {
+        let err = collect_replacements("<c-card>hi").unwrap_err();
+        assert!(err.to_string().contains("Unclosed tag <c-card>"));
+    }
+
+    #[test]
+    fn collect_replacements_rejects_mismatched_closing_tag() {
+        let err = collect_replacements("<c-card></c-icon>").unwrap_err();
+        assert!(err.to_string().contains("Mismatched closing tag"));
+    }
+
+    #[test]
+    fn namespaced_attribute_shorthand_parses_to_attr_namespace() {
+        let attrs = parse_attributes(r#":title="record.title" plain="x""#);
+        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs[0].namespace.as_deref(), Some(":"));
+        assert_eq!(attrs[0].key, "title");
+        assert_eq!(attrs[0].value.as_deref(), Some("record.title"));
+        assert_eq!(attrs[1].namespace, None);
+    }
+
+    #[test]
+    fn namespaced_attribute_shorthand_compiles_to_attr_block() {
+        let tag = parse_c_tag(r#"<c-card :title="record.title">"#, 0)
+            .unwrap()
+            .unwrap();
+        let (replacement, _) = process_component(&tag).unwrap();
+        assert!(replacement.contains("{% attr title %}record.title{% endattr %}"));
+    }
+}